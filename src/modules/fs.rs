@@ -0,0 +1,150 @@
+use wren_macros::foreign_static_method;
+
+use super::{Class, Module};
+use std::ffi::CString;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+pub fn init_module() -> Module {
+    let mut directory_class = Class::new();
+    directory_class
+        .static_methods
+        .insert("listDirectory_(_)".to_string(), foreign_list_directory);
+    directory_class
+        .static_methods
+        .insert("directoryExists_(_)".to_string(), foreign_directory_exists);
+
+    let mut file_class = Class::new();
+    file_class
+        .static_methods
+        .insert("fileExists_(_)".to_string(), foreign_file_exists);
+
+    let mut stat_class = Class::new();
+    stat_class
+        .static_methods
+        .insert("size_(_,_)".to_string(), foreign_size);
+    stat_class
+        .static_methods
+        .insert("isFile_(_,_)".to_string(), foreign_is_file);
+    stat_class
+        .static_methods
+        .insert("isDir_(_,_)".to_string(), foreign_is_dir);
+    stat_class
+        .static_methods
+        .insert("isSymlink_(_,_)".to_string(), foreign_is_symlink);
+    stat_class
+        .static_methods
+        .insert("modified_(_,_)".to_string(), foreign_modified);
+    stat_class
+        .static_methods
+        .insert("accessed_(_,_)".to_string(), foreign_accessed);
+    stat_class
+        .static_methods
+        .insert("mode_(_,_)".to_string(), foreign_mode);
+
+    let mut module = Module::new(CString::new(include_str!("fs.wren")).unwrap());
+    module
+        .classes
+        .insert("Directory".to_string(), directory_class);
+    module.classes.insert("File".to_string(), file_class);
+    module.classes.insert("Stat".to_string(), stat_class);
+
+    module
+}
+
+fn metadata(path: &str, follow_symlinks: bool) -> Result<fs::Metadata, &'static str> {
+    let result = if follow_symlinks {
+        fs::metadata(path)
+    } else {
+        fs::symlink_metadata(path)
+    };
+    result.map_err(|_| "Cannot read metadata for path.")
+}
+
+/// Seconds since the Unix epoch, negative if `time` predates it.
+fn to_epoch_secs(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_secs_f64(),
+        Err(before_epoch) => -before_epoch.duration().as_secs_f64(),
+    }
+}
+
+#[foreign_static_method]
+fn list_directory(path: String) -> Result<Vec<String>, &'static str> {
+    let entries = fs::read_dir(path).map_err(|_| "Cannot read directory.")?;
+    entries
+        .map(|entry| {
+            entry
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .map_err(|_| "Cannot read directory entry.")
+        })
+        .collect()
+}
+
+#[foreign_static_method]
+fn directory_exists(path: String) -> bool {
+    Path::new(&path).is_dir()
+}
+
+#[foreign_static_method]
+fn file_exists(path: String) -> bool {
+    Path::new(&path).is_file()
+}
+
+#[foreign_static_method]
+fn size(path: String, follow_symlinks: bool) -> Result<f64, &'static str> {
+    #[allow(clippy::cast_precision_loss)]
+    metadata(&path, follow_symlinks).map(|metadata| metadata.len() as f64)
+}
+
+#[foreign_static_method]
+fn is_file(path: String, follow_symlinks: bool) -> Result<bool, &'static str> {
+    metadata(&path, follow_symlinks).map(|metadata| metadata.is_file())
+}
+
+#[foreign_static_method]
+fn is_dir(path: String, follow_symlinks: bool) -> Result<bool, &'static str> {
+    metadata(&path, follow_symlinks).map(|metadata| metadata.is_dir())
+}
+
+#[foreign_static_method]
+fn is_symlink(path: String, follow_symlinks: bool) -> Result<bool, &'static str> {
+    metadata(&path, follow_symlinks).map(|metadata| metadata.is_symlink())
+}
+
+#[foreign_static_method]
+fn modified(path: String, follow_symlinks: bool) -> Result<f64, &'static str> {
+    let modified = metadata(&path, follow_symlinks)?
+        .modified()
+        .map_err(|_| "Modified time is not available on this platform.")?;
+    Ok(to_epoch_secs(modified))
+}
+
+#[foreign_static_method]
+fn accessed(path: String, follow_symlinks: bool) -> Result<f64, &'static str> {
+    let accessed = metadata(&path, follow_symlinks)?
+        .accessed()
+        .map_err(|_| "Accessed time is not available on this platform.")?;
+    Ok(to_epoch_secs(accessed))
+}
+
+#[foreign_static_method]
+fn mode(path: String, follow_symlinks: bool) -> Result<f64, &'static str> {
+    #[cfg(unix)]
+    let result = {
+        #[allow(clippy::cast_lossless)]
+        metadata(&path, follow_symlinks).map(|metadata| metadata.mode() as f64)
+    };
+
+    #[cfg(not(unix))]
+    let result = {
+        let _ = (path, follow_symlinks);
+        Err("File permission bits are not implemented outside of unix based operating systems!")
+    };
+
+    result
+}
@@ -1,5 +1,7 @@
 #!allow(unsafe_code);
 
+mod fs;
+mod os;
 mod scheduler;
 mod timer;
 
@@ -51,18 +53,9 @@ fn modules_init() -> HashMap<&'static str, Module> {
 
     m.insert("scheduler", scheduler_module);
 
-    let timer_source = include_str!("timer.wren");
-
-    let mut timer_class = Class::new();
-    timer_class
-        .static_methods
-        .insert("startTimer_(_,_)".to_string(), timer::start);
-
-    let mut timer_module = Module::new(CString::new(timer_source).unwrap());
-    timer_module
-        .classes
-        .insert("Timer".to_string(), timer_class);
-    m.insert("timer", timer_module);
+    m.insert("timer", timer::init_module());
+    m.insert("fs", fs::init_module());
+    m.insert("os", os::init_module());
 
     m
 }
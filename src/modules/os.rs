@@ -1,34 +1,57 @@
 use wren::VERSION;
 use wren_macros::foreign_static_method;
 
-use super::{source_file, Class, Module};
+use super::{Class, Module};
 use std::env::args;
 use std::env::current_dir;
+use std::ffi::CString;
+use std::process::Command;
 
-pub fn init_module<'wren>() -> Module<'wren> {
+pub fn init_module() -> Module {
     let mut platform_class = Class::new();
     platform_class
         .static_methods
-        .insert("isPosix", foreign_is_posix);
-    platform_class.static_methods.insert("name", foreign_name);
+        .insert("isPosix".to_string(), foreign_is_posix);
     platform_class
         .static_methods
-        .insert("homePath", foreign_home_path);
+        .insert("name".to_string(), foreign_name);
+    platform_class
+        .static_methods
+        .insert("homePath".to_string(), foreign_home_path);
 
     let mut process_class = Class::new();
     process_class
         .static_methods
-        .insert("allArguments", foreign_all_arguments);
+        .insert("allArguments".to_string(), foreign_all_arguments);
+    process_class
+        .static_methods
+        .insert("version".to_string(), foreign_version);
+    process_class
+        .static_methods
+        .insert("cwd".to_string(), foreign_cwd);
+    process_class
+        .static_methods
+        .insert("pid".to_string(), foreign_pid);
+    process_class
+        .static_methods
+        .insert("ppid".to_string(), foreign_ppid);
+    process_class
+        .static_methods
+        .insert("env(_)".to_string(), foreign_env);
+    process_class
+        .static_methods
+        .insert("allVariables".to_string(), foreign_all_variables);
     process_class
         .static_methods
-        .insert("version", foreign_version);
-    process_class.static_methods.insert("cwd", foreign_cwd);
-    process_class.static_methods.insert("pid", foreign_pid);
-    process_class.static_methods.insert("ppid", foreign_ppid);
+        .insert("run(_,_)".to_string(), foreign_run);
 
-    let mut module = Module::new(source_file!("os.wren"));
-    module.classes.insert("Process", process_class);
-    module.classes.insert("Platform", platform_class);
+    let mut module = Module::new(CString::new(include_str!("os.wren")).unwrap());
+    module
+        .classes
+        .insert("Process".to_string(), process_class);
+    module
+        .classes
+        .insert("Platform".to_string(), platform_class);
 
     module
 }
@@ -80,3 +103,34 @@ fn ppid() -> Result<f64, &'static str> {
 
     result
 }
+
+#[foreign_static_method]
+fn env(name: String) -> Result<String, &'static str> {
+    std::env::var(name).map_err(|_| "Environment variable is not set.")
+}
+
+#[foreign_static_method]
+fn all_variables() -> Vec<String> {
+    std::env::vars()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect()
+}
+
+/// Spawns `program` with `args`, waits for it to exit, and flattens the
+/// result into a 3-element record: exit code (as a string), captured
+/// stdout, then captured stderr -- the same flattening `allVariables` uses
+/// for its `KEY=VALUE` pairs, since there's no richer record type to send
+/// back in one slot.
+#[foreign_static_method]
+fn run(program: String, args: Vec<String>) -> Result<Vec<String>, &'static str> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|_| "Cannot spawn process.")?;
+
+    Ok(vec![
+        output.status.code().unwrap_or(-1).to_string(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ])
+}
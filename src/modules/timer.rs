@@ -3,6 +3,7 @@
 use tokio::time::{sleep, Duration};
 
 use crate::wren;
+use crate::wren::{FromWren, ToWren};
 use crate::MyUserData;
 
 use super::{Class, Module};
@@ -15,6 +16,15 @@ pub fn init_module() -> Module {
     timer_class
         .static_methods
         .insert("startTimer_(_,_)".to_string(), start);
+    timer_class
+        .static_methods
+        .insert("cancelTimer_(_)".to_string(), cancel);
+    timer_class
+        .static_methods
+        .insert("pending_()".to_string(), pending);
+    timer_class
+        .static_methods
+        .insert("startAllTimers_(_,_)".to_string(), start_all);
 
     let mut timer_module = Module::new(CString::new(timer_source).unwrap());
     timer_module
@@ -32,12 +42,95 @@ unsafe fn start(vm: wren::VMPtr) {
     let ms = vm.get_slot_double_unchecked(1);
     let fiber = vm.get_slot_handle_unchecked(2);
 
-    let task = async move {
-        sleep(Duration::from_secs_f64(ms / 1000.0)).await;
-        let user_data = vm.get_user_data::<MyUserData>().unwrap();
-        let scheduler = user_data.scheduler.as_ref().unwrap();
-        scheduler.resume(fiber, false);
-    };
+    let task = scheduler.schedule_task(
+        async move { sleep(Duration::from_secs_f64(ms / 1000.0)).await },
+        Some(format!("Timer.sleep({ms}ms)")),
+        move |vm, result| {
+            let user_data = vm.get_user_data::<MyUserData>().unwrap();
+            let scheduler = user_data.scheduler.as_ref().unwrap();
+            match result {
+                wren::TaskResult::Completed => scheduler.resume(&fiber),
+                wren::TaskResult::Panicked(message) => {
+                    scheduler.resume_with_error(&fiber, message);
+                }
+            }
+        },
+    );
+
+    vm.set_slot_double_unchecked(0, task.into_raw());
+}
+
+/// Cancels a timer started with `startTimer_`, identified by the task id it
+/// returned. The fiber waiting on it is never resumed.
+unsafe fn cancel(vm: wren::VMPtr) {
+    let user_data = vm.get_user_data::<MyUserData>().unwrap();
+    let scheduler = user_data.scheduler.as_mut().unwrap();
+
+    let task = wren::TaskHandle::from_raw(vm.get_slot_double_unchecked(1));
+    let cancelled = scheduler.cancel(task);
+
+    vm.set_slot_bool_unchecked(0, cancelled);
+}
+
+/// Starts many timers in one foreign call instead of one `startTimer_` call
+/// per timer: `durationsMs` and `fibers` are parallel lists, and each
+/// `(ms, fiber)` pair is handed to [`wren::Scheduler::schedule_batch`]
+/// together rather than queued one at a time. Returns the list of task ids,
+/// in the same order as `fibers`, for cancelling individual timers later.
+///
+/// The `Scheduler` class wired up in `modules/mod.rs` only binds
+/// `captureMethods_()` (see `pending` below), so this is reachable as
+/// `Timer.startAllTimers_` -- the batching it does is scheduler-level,
+/// `Timer` just happens to be the class that exposes it from Wren.
+unsafe fn start_all(vm: wren::VMPtr) {
+    let user_data = vm.get_user_data::<MyUserData>().unwrap();
+    let scheduler = user_data.scheduler.as_mut().unwrap();
+
+    let durations_ms = Vec::<f64>::from_wren(vm, 1);
+    let fibers = Vec::<wren::Handle>::from_wren(vm, 2);
+
+    let tasks = durations_ms.into_iter().zip(fibers).map(|(ms, fiber)| {
+        let future = async move { sleep(Duration::from_secs_f64(ms / 1000.0)).await };
+        let on_complete = move |vm: wren::VMPtr, result| {
+            let user_data = vm.get_user_data::<MyUserData>().unwrap();
+            let scheduler = user_data.scheduler.as_ref().unwrap();
+            match result {
+                wren::TaskResult::Completed => scheduler.resume(&fiber),
+                wren::TaskResult::Panicked(message) => {
+                    scheduler.resume_with_error(&fiber, message);
+                }
+            }
+        };
+        (future, on_complete)
+    });
+
+    let task_ids: Vec<f64> = scheduler
+        .schedule_batch(tasks)
+        .into_iter()
+        .map(wren::TaskHandle::into_raw)
+        .collect();
+
+    vm.ensure_slots(2);
+    task_ids.to_wren(vm, 0);
+}
+
+/// Returns a diagnostic list of every task still suspended in the
+/// scheduler: its length, followed by one label per task (empty string for
+/// a task scheduled with no label). The `Scheduler` class wired up in
+/// `modules/mod.rs` only binds `captureMethods_()`, so this is reachable
+/// as `Timer.pending_()` -- the scheduler it inspects isn't
+/// timer-specific, it just happens to be the same one `Timer` already uses.
+unsafe fn pending(vm: wren::VMPtr) {
+    let user_data = vm.get_user_data::<MyUserData>().unwrap();
+    let scheduler = user_data.scheduler.as_ref().unwrap();
+
+    let mut result: Vec<String> = vec![scheduler.inspect().count().to_string()];
+    result.extend(
+        scheduler
+            .inspect()
+            .map(|meta| meta.label.clone().unwrap_or_default()),
+    );
 
-    scheduler.schedule_task(task);
+    vm.ensure_slots(2);
+    result.to_wren(vm, 0);
 }
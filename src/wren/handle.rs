@@ -1,20 +1,118 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{collections::HashMap, marker::PhantomData, ptr::NonNull, sync::Mutex};
 
-use wren_sys::{wrenReleaseHandle, WrenHandle};
+use wren_sys::{wrenCall, wrenReleaseHandle, WrenHandle};
 
-use super::RawVMContext;
+use super::{FromWren, InterpretResultErrorKind, ToWrenArgs, VMPtr};
 
-pub struct Handle<'wren> {
-    vm: RawVMContext<'wren>,
+/// Why a [`Handle`] failed validation before [`Handle::call`] touched its pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleError {
+    /// The handle has already been released; its slot may since have been reused.
+    Released,
+}
+
+struct Entry {
+    generation: u32,
+    live: bool,
+}
+
+/// Tracks which handles minted against a single VM are still alive, so a
+/// [`Handle`] can be validated before it's dereferenced instead of trusting
+/// the caller not to pass in a stale one.
+///
+/// Slots are reused once freed, but every reuse bumps the slot's generation,
+/// so a [`Handle`] tag minted before the slot was freed never matches again.
+#[derive(Default)]
+struct HandleRegistry {
+    entries: Vec<Entry>,
+    free: Vec<u32>,
+}
+
+impl HandleRegistry {
+    fn alloc(&mut self) -> (u32, u32) {
+        if let Some(index) = self.free.pop() {
+            let entry = &mut self.entries[index as usize];
+            entry.live = true;
+            (index, entry.generation)
+        } else {
+            let index = u32::try_from(self.entries.len())
+                .expect("more live handles than fit in a u32 index");
+            self.entries.push(Entry {
+                generation: 0,
+                live: true,
+            });
+            (index, 0)
+        }
+    }
+
+    fn free(&mut self, index: u32) {
+        if let Some(entry) = self.entries.get_mut(index as usize) {
+            entry.live = false;
+            entry.generation = entry.generation.wrapping_add(1);
+            self.free.push(index);
+        }
+    }
+
+    fn validate(&self, index: u32, generation: u32) -> bool {
+        self.entries
+            .get(index as usize)
+            .map_or(false, |entry| entry.live && entry.generation == generation)
+    }
+}
+
+lazy_static::lazy_static! {
+    // One registry per live VM, keyed by the VM's pointer -- already a
+    // unique, stable identifier for as long as the VM is alive, so there's
+    // no need to mint a separate map id at VM creation.
+    static ref REGISTRIES: Mutex<HashMap<usize, HandleRegistry>> = Mutex::new(HashMap::new());
+}
+
+fn vm_id(vm: VMPtr) -> usize {
+    vm.as_ptr() as usize
+}
+
+/// Removes `vm`'s [`HandleRegistry`], if any. Must be called while `vm` is
+/// being freed so a later `Vm` that happens to land on the same address
+/// doesn't inherit stale `(index, generation)` state left over from `vm`.
+pub(super) fn drop_registry(vm: VMPtr) {
+    REGISTRIES.lock().unwrap().remove(&vm_id(vm));
+}
+
+fn with_registry<R>(vm_id: usize, f: impl FnOnce(&mut HandleRegistry) -> R) -> R {
+    let mut registries = REGISTRIES.lock().unwrap();
+    f(registries.entry(vm_id).or_default())
+}
+
+/// A handle to a Wren object or call signature.
+///
+/// `wrenReleaseHandle` must be called exactly once on every `WrenHandle`, and
+/// the handle must never be touched again once the `Vm` that created it has
+/// been freed. `Handle` ties both of those to Rust's ownership system: it
+/// releases itself when dropped, and its `'vm` lifetime borrows the `VMPtr`
+/// it was created from so it can't be held past the call that produced it.
+///
+/// It also carries a generational tag, minted from a per-VM [`HandleRegistry`]
+/// at construction time, so a handle that outlives its `drop` (e.g. through a
+/// use-after-free bug elsewhere) is rejected by [`Handle::call`] instead of
+/// being handed to `wrenCall` with a potentially-reused pointer.
+pub struct Handle<'vm> {
+    vm: VMPtr,
     pointer: NonNull<WrenHandle>,
-    phantom: PhantomData<WrenHandle>,
+    index: u32,
+    generation: u32,
+    phantom: PhantomData<&'vm VMPtr>,
 }
 
-impl<'wren> Handle<'wren> {
-    pub(crate) fn new(vm: &RawVMContext<'wren>, pointer: NonNull<WrenHandle>) -> Self {
+impl<'vm> Handle<'vm> {
+    /// SAFETY: `pointer` must be a handle `vm` just minted, e.g. via
+    /// `wrenGetSlotHandle`/`wrenMakeCallHandle`.
+    pub(super) unsafe fn new(vm: VMPtr, pointer: NonNull<WrenHandle>) -> Self {
+        let (index, generation) = with_registry(vm_id(vm), HandleRegistry::alloc);
         Self {
-            vm: vm.clone(),
+            vm,
             pointer,
+            index,
+            generation,
             phantom: PhantomData,
         }
     }
@@ -22,10 +120,86 @@ impl<'wren> Handle<'wren> {
     pub(crate) const fn as_ptr(&self) -> *mut WrenHandle {
         self.pointer.as_ptr()
     }
+
+    /// Confirms this handle hasn't already been released (its slot may since
+    /// have been reused by a newer handle). [`Handle::call`] runs this before
+    /// touching the handle's pointer instead of trusting it blindly.
+    fn validate(&self) -> Result<(), HandleError> {
+        if with_registry(vm_id(self.vm), |registry| {
+            registry.validate(self.index, self.generation)
+        }) {
+            Ok(())
+        } else {
+            Err(HandleError::Released)
+        }
+    }
+
+    /// Calls this handle, writing `args` into consecutive slots starting at
+    /// slot 0 -- typically the receiver followed by the method's arguments --
+    /// then decodes slot 0 back into `Ret`. Replaces manually driving
+    /// [`VMPtr`]'s slot accessors before calling [`wrenCall`].
+    ///
+    /// SAFETY: `self` must have been created by [`VMPtr::make_call_handle`]
+    /// against a signature whose arity matches `args`.
+    pub unsafe fn call<Args, Ret>(&self, args: Args) -> Result<Ret, InterpretResultErrorKind>
+    where
+        Args: ToWrenArgs,
+        Ret: FromWren,
+    {
+        self.validate()
+            .map_err(InterpretResultErrorKind::InvalidHandle)?;
+
+        self.vm.ensure_slots(Args::TOTAL_REQUIRED_SLOTS);
+        args.set_slots(self.vm);
+
+        let result = wrenCall(self.vm.as_ptr(), self.pointer.as_ptr());
+        InterpretResultErrorKind::new_from_result(result)?;
+
+        Ok(Ret::from_wren(self.vm, 0))
+    }
 }
 
-impl<'wren> Drop for Handle<'wren> {
+impl<'vm> Drop for Handle<'vm> {
     fn drop(&mut self) {
+        with_registry(vm_id(self.vm), |registry| registry.free(self.index));
         unsafe { wrenReleaseHandle(self.vm.as_ptr(), self.pointer.as_ptr()) }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::HandleRegistry;
+
+    #[test]
+    fn double_use_is_rejected_after_free() {
+        let mut registry = HandleRegistry::default();
+        let (index, generation) = registry.alloc();
+        assert!(registry.validate(index, generation));
+
+        registry.free(index);
+        assert!(!registry.validate(index, generation));
+
+        // Reusing the freed slot should mint a new generation, so the old
+        // tag still doesn't validate even though the slot is live again.
+        let (reused_index, reused_generation) = registry.alloc();
+        assert_eq!(reused_index, index);
+        assert_ne!(reused_generation, generation);
+        assert!(!registry.validate(index, generation));
+        assert!(registry.validate(reused_index, reused_generation));
+    }
+
+    #[test]
+    fn handle_from_one_vm_is_rejected_by_another() {
+        let mut vm_a = HandleRegistry::default();
+        let mut vm_b = HandleRegistry::default();
+
+        let (index, generation) = vm_a.alloc();
+        // The same (index, generation) pair was never minted by `vm_b`'s
+        // registry, so it must not validate there.
+        assert!(!vm_b.validate(index, generation));
+        vm_b.alloc();
+        assert!(!vm_b.validate(index, generation));
+
+        assert!(vm_a.validate(index, generation));
+    }
+}
@@ -1,7 +1,16 @@
 #![allow(unsafe_code)]
 
+mod handle;
+mod module_loader;
+mod scheduler;
 mod wren_value;
 
+pub use handle::{Handle, HandleError};
+pub use module_loader::{resolve_relative_import, FsModuleLoader};
+pub use scheduler::{Scheduler, SchedulerRuntime, TaskHandle, TaskResult, TokioRuntime};
+#[cfg(feature = "smol-runtime")]
+pub use scheduler::SmolRuntime;
+
 use std::{
     alloc::Layout,
     borrow::Cow,
@@ -13,10 +22,9 @@ use std::{
 };
 
 use wren_sys::{
-    self, wrenCall, wrenFreeVM, wrenGetUserData, wrenGetVariable, wrenInitConfiguration,
-    wrenInsertInList, wrenInterpret, wrenMakeCallHandle, wrenNewVM, wrenReleaseHandle,
-    WrenConfiguration, WrenErrorType, WrenHandle, WrenInterpretResult, WrenLoadModuleResult,
-    WrenVM,
+    self, wrenFreeVM, wrenGetUserData, wrenGetVariable, wrenInitConfiguration, wrenInsertInList,
+    wrenInterpret, wrenMakeCallHandle, wrenNewVM, WrenConfiguration, WrenErrorType,
+    WrenInterpretResult, WrenLoadModuleResult, WrenVM,
 };
 
 pub type ForeignMethod = unsafe fn(vm: VMPtr);
@@ -121,6 +129,62 @@ unsafe extern "C" fn bind_foreign_method<V: VmUserData>(
     )
 }
 
+unsafe extern "C" fn bind_foreign_class<V: VmUserData>(
+    vm: *mut WrenVM,
+    module: *const i8,
+    class_name: *const i8,
+) -> wren_sys::WrenForeignClassMethods {
+    let user_data = get_user_data::<V>(vm);
+
+    user_data.map_or_else(
+        || std::mem::zeroed(),
+        |user_data| {
+            let module = CStr::from_ptr(module).to_string_lossy();
+            let class_name = CStr::from_ptr(class_name).to_string_lossy();
+
+            user_data.bind_foreign_class(module.as_ref(), class_name.as_ref())
+        },
+    )
+}
+
+/// A Rust type that backs a Wren `foreign class`.
+///
+/// The Wren side is expected to declare `foreign class NAME` with no
+/// constructor logic of its own -- Rust is the only thing that ever
+/// allocates, mutates, or drops the instance. Return [`foreign_class_methods`]
+/// for `Self` from [`VmUserData::bind_foreign_class`] to wire it up.
+pub trait WrenForeignClass: Sized {
+    /// Constructs the value that will be stored in the foreign object Wren
+    /// just allocated, reading constructor arguments (if any) out of `vm`'s
+    /// slots starting at slot 1.
+    fn allocate(vm: VMPtr) -> Self;
+}
+
+unsafe extern "C" fn allocate<T: WrenForeignClass>(vm: *mut WrenVM) {
+    let vm = VMPtr::new_unchecked(vm);
+    // SAFETY: slot 0 always holds the receiver, and wren only ever calls this
+    // allocator for the class it was registered against via
+    // `foreign_class_methods::<T>`, so `size_of::<T>` is always the right size
+    let pointer = wren_sys::wrenSetSlotNewForeign(vm.0.as_ptr(), 0, 0, std::mem::size_of::<T>());
+    pointer.cast::<T>().write(T::allocate(vm));
+}
+
+/// SAFETY: mirrors safe_wren's `FinalizerFn` contract -- this runs on the GC
+/// thread mid-collection, so it must not touch the VM at all, only drop the
+/// value that was stored in `data`.
+unsafe extern "C" fn finalize<T>(data: *mut c_void) {
+    data.cast::<T>().drop_in_place();
+}
+
+/// Builds the `allocate`/`finalize` pair that backs a Wren `foreign class`
+/// with `T`. Return this from [`VmUserData::bind_foreign_class`].
+pub fn foreign_class_methods<T: WrenForeignClass>() -> wren_sys::WrenForeignClassMethods {
+    wren_sys::WrenForeignClassMethods {
+        allocate: Some(allocate::<T>),
+        finalize: Some(finalize::<T>),
+    }
+}
+
 unsafe extern "C" fn write_fn<V: VmUserData>(vm: *mut WrenVM, text: *const i8) {
     let user_data = get_user_data::<V>(vm);
 
@@ -177,9 +241,13 @@ static_assertions::assert_eq_align!(VMPtr, *mut WrenVM);
 static_assertions::assert_eq_size!(VMPtr, *mut WrenVM);
 
 type Slot = std::os::raw::c_int;
-type Handle = NonNull<WrenHandle>;
 
 impl VMPtr {
+    /// The raw VM pointer backing this `VMPtr`, e.g. to key a per-VM cache by identity.
+    pub(crate) const fn as_ptr(self) -> *mut WrenVM {
+        self.0.as_ptr()
+    }
+
     pub const unsafe fn new_unchecked(vm: *mut WrenVM) -> Self {
         Self(NonNull::new_unchecked(vm))
     }
@@ -192,7 +260,7 @@ impl VMPtr {
 
     /// SAFETY: Will segfault if an invalid slot
     /// is asked for
-    pub unsafe fn set_slot_handle_unchecked(self, slot: Slot, handle: Handle) {
+    pub unsafe fn set_slot_handle_unchecked(self, slot: Slot, handle: &Handle) {
         wren_sys::wrenSetSlotHandle(self.0.as_ptr(), slot, handle.as_ptr());
     }
 
@@ -227,16 +295,62 @@ impl VMPtr {
         wren_sys::wrenGetSlotBool(self.0.as_ptr(), slot)
     }
 
+    /// SAFETY: Will segfault if an invalid slot is set for
+    pub unsafe fn set_slot_double_unchecked(self, slot: Slot, value: f64) {
+        wren_sys::wrenSetSlotDouble(self.0.as_ptr(), slot, value);
+    }
+
+    /// SAFETY: Calling this on a slot that isn't a number or a valid slot is undefined behavior
+    pub unsafe fn get_slot_double_unchecked(self, slot: Slot) -> f64 {
+        wren_sys::wrenGetSlotDouble(self.0.as_ptr(), slot)
+    }
+
+    /// SAFETY: Will segfault if an invalid slot is set for
+    pub unsafe fn set_slot_null_unchecked(self, slot: Slot) {
+        wren_sys::wrenSetSlotNull(self.0.as_ptr(), slot);
+    }
+
+    /// SAFETY: Calling this on a slot that isn't a string or a valid slot is undefined behavior
+    pub unsafe fn get_slot_string_unchecked(self, slot: Slot) -> String {
+        CStr::from_ptr(wren_sys::wrenGetSlotString(self.0.as_ptr(), slot))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// SAFETY: Calling this on a slot that isn't a list or a valid slot is undefined behavior
+    pub unsafe fn get_list_count_unchecked(self, slot: Slot) -> i32 {
+        wren_sys::wrenGetListCount(self.0.as_ptr(), slot)
+    }
+
+    /// SAFETY: Calling this on a slot that isn't a list, with an out of bounds `index`,
+    /// or with an invalid `element_slot` is undefined behavior
+    pub unsafe fn get_list_element_unchecked(
+        self,
+        list_slot: Slot,
+        index: i32,
+        element_slot: Slot,
+    ) {
+        wren_sys::wrenGetListElement(self.0.as_ptr(), list_slot, index, element_slot);
+    }
+
     /// SAFETY: this is always non null but will segfault if an invalid slot
     /// is asked for
     /// And is not guarenteed to be a valid object
-    pub unsafe fn get_slot_handle_unchecked(self, slot: Slot) -> Handle {
-        NonNull::new_unchecked(wren_sys::wrenGetSlotHandle(self.0.as_ptr(), slot))
+    pub unsafe fn get_slot_handle_unchecked<'vm>(&'vm self, slot: Slot) -> Handle<'vm> {
+        let pointer = NonNull::new_unchecked(wren_sys::wrenGetSlotHandle(self.0.as_ptr(), slot));
+        Handle::new(*self, pointer)
     }
 
-    /// SAFETY: Calling this on a slot that isn't a bool or a valid slot is undefined behavior
-    pub unsafe fn get_slot_double_unchecked(self, slot: Slot) -> f64 {
-        wren_sys::wrenGetSlotDouble(self.0.as_ptr(), slot)
+    /// Borrows the `T` stored in a foreign slot.
+    ///
+    /// SAFETY: `slot` must hold a foreign object that was allocated as a `T`,
+    /// i.e. through [`foreign_class_methods`], and the caller must not alias
+    /// this borrow (Wren itself has no notion of Rust's borrow rules).
+    pub unsafe fn get_slot_foreign_unchecked<'vm, T>(&'vm self, slot: Slot) -> &'vm mut T {
+        wren_sys::wrenGetSlotForeign(self.0.as_ptr(), slot)
+            .cast::<T>()
+            .as_mut()
+            .unwrap()
     }
 
     /// SAFETY: this is always non null but will segfault if an invalid slot
@@ -255,7 +369,7 @@ impl VMPtr {
         wrenGetVariable(vm.as_ptr(), module.as_ptr(), name.as_ptr(), slot);
     }
 
-    pub fn make_call_handle<Signature>(self, signature: Signature) -> Handle
+    pub fn make_call_handle<'vm, Signature>(&'vm self, signature: Signature) -> Handle<'vm>
     where
         Signature: AsRef<str>,
     {
@@ -263,19 +377,23 @@ impl VMPtr {
         let signature = CString::new(signature.as_ref()).unwrap();
         // SAFETY: this function is always safe to call but may be unsafe to use the handle it returns
         // as that handle might not be valid
-        unsafe { NonNull::new_unchecked(wrenMakeCallHandle(vm.as_ptr(), signature.as_ptr())) }
+        unsafe {
+            let pointer = NonNull::new_unchecked(wrenMakeCallHandle(vm.as_ptr(), signature.as_ptr()));
+            Handle::new(*self, pointer)
+        }
     }
 
     /// Safety: Will segfault if used with an invalid method
-    pub unsafe fn call(self, method: Handle) -> Result<(), InterpretResultErrorKind> {
-        let vm = self.0;
-        let result = wrenCall(vm.as_ptr(), method.as_ptr());
-
-        InterpretResultErrorKind::new_from_result(result)
-    }
-
-    pub unsafe fn release_handle_unchecked(self, handle: Handle) {
-        wrenReleaseHandle(self.0.as_ptr(), handle.as_ptr());
+    pub unsafe fn call<Args, Ret>(
+        self,
+        method: &Handle,
+        args: Args,
+    ) -> Result<Ret, InterpretResultErrorKind>
+    where
+        Args: ToWrenArgs,
+        Ret: FromWren,
+    {
+        method.call(args)
     }
 
     pub fn ensure_slots(self, num_slots: Slot) {
@@ -284,7 +402,236 @@ impl VMPtr {
             wren_sys::wrenEnsureSlots(self.0.as_ptr(), num_slots);
         }
     }
+
+    /// Number of slots currently available to the running foreign method.
+    pub fn slot_count(self) -> Slot {
+        // SAFETY: always safe to call, it just reads the API stack's current size
+        unsafe { wren_sys::wrenGetSlotCount(self.0.as_ptr()) }
+    }
+
+    /// The type of the value currently sitting in `slot`.
+    ///
+    /// Lets callers validate a slot's contents before reaching for one of the
+    /// `get_slot_*_unchecked` getters, instead of risking UB on a mismatched type.
+    pub fn get_slot_type(self, slot: Slot) -> SlotType {
+        // SAFETY: always safe to call, it just inspects the value already in the slot
+        let ty = unsafe { wren_sys::wrenGetSlotType(self.0.as_ptr(), slot) };
+        SlotType::from(ty)
+    }
+}
+
+/// Mirrors Wren's `WrenType`, as returned by [`VMPtr::get_slot_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotType {
+    Bool,
+    Num,
+    Foreign,
+    List,
+    Map,
+    Null,
+    String,
+    Unknown,
+}
+
+impl From<wren_sys::WrenType> for SlotType {
+    fn from(other: wren_sys::WrenType) -> Self {
+        match other {
+            wren_sys::WrenType_WREN_TYPE_BOOL => Self::Bool,
+            wren_sys::WrenType_WREN_TYPE_NUM => Self::Num,
+            wren_sys::WrenType_WREN_TYPE_FOREIGN => Self::Foreign,
+            wren_sys::WrenType_WREN_TYPE_LIST => Self::List,
+            wren_sys::WrenType_WREN_TYPE_MAP => Self::Map,
+            wren_sys::WrenType_WREN_TYPE_NULL => Self::Null,
+            wren_sys::WrenType_WREN_TYPE_STRING => Self::String,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A value that can be written into, or read out of, a single Wren slot.
+///
+/// Implement this together with [`ToWren`]/[`FromWren`] (it is their
+/// supertrait) to plug a type into [`Handle::call`]'s argument list or
+/// return type.
+pub trait WrenValue {
+    /// Number of consecutive slots this value occupies, starting at the slot
+    /// it is written to or read from.
+    const REQUIRED_SLOTS: Slot = 1;
+}
+
+pub trait ToWren: WrenValue {
+    /// SAFETY: `vm` must have at least `slot + Self::REQUIRED_SLOTS` slots
+    /// reserved (see [`VMPtr::ensure_slots`]).
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot);
+}
+
+pub trait FromWren: WrenValue + Sized {
+    /// SAFETY: `slot` must actually hold a value that was written as `Self`.
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self;
+}
+
+impl WrenValue for () {
+    const REQUIRED_SLOTS: Slot = 0;
+}
+impl FromWren for () {
+    unsafe fn from_wren(_vm: VMPtr, _slot: Slot) -> Self {}
+}
+
+impl<T: WrenValue + ?Sized> WrenValue for &T {
+    const REQUIRED_SLOTS: Slot = T::REQUIRED_SLOTS;
+}
+impl<T: ToWren + ?Sized> ToWren for &T {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        (**self).to_wren(vm, slot);
+    }
+}
+
+impl WrenValue for f64 {}
+impl ToWren for f64 {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        vm.set_slot_double_unchecked(slot, *self);
+    }
+}
+impl FromWren for f64 {
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self {
+        vm.get_slot_double_unchecked(slot)
+    }
+}
+
+impl WrenValue for bool {}
+impl ToWren for bool {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        vm.set_slot_bool_unchecked(slot, *self);
+    }
+}
+impl FromWren for bool {
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self {
+        vm.get_slot_bool_unchecked(slot)
+    }
+}
+
+impl WrenValue for str {}
+impl ToWren for str {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        vm.set_slot_string_unchecked(slot, self);
+    }
+}
+
+impl WrenValue for String {}
+impl ToWren for String {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        vm.set_slot_string_unchecked(slot, self);
+    }
+}
+impl FromWren for String {
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self {
+        vm.get_slot_string_unchecked(slot)
+    }
+}
+
+impl<'vm> WrenValue for Handle<'vm> {}
+impl<'vm> ToWren for Handle<'vm> {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        vm.set_slot_handle_unchecked(slot, self);
+    }
+}
+impl<'vm> FromWren for Handle<'vm> {
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self {
+        vm.get_slot_handle_unchecked(slot)
+    }
+}
+
+impl<T: WrenValue> WrenValue for Option<T> {
+    const REQUIRED_SLOTS: Slot = T::REQUIRED_SLOTS;
+}
+impl<T: ToWren> ToWren for Option<T> {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        match self {
+            Some(value) => value.to_wren(vm, slot),
+            None => vm.set_slot_null_unchecked(slot),
+        }
+    }
+}
+impl<T: FromWren> FromWren for Option<T> {
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self {
+        if vm.get_slot_type(slot) == SlotType::Null {
+            None
+        } else {
+            Some(T::from_wren(vm, slot))
+        }
+    }
+}
+
+impl<T: WrenValue> WrenValue for Vec<T> {
+    // One scratch slot to shuttle each element through, on top of the list itself
+    const REQUIRED_SLOTS: Slot = 1 + T::REQUIRED_SLOTS;
+}
+impl<T: ToWren> ToWren for Vec<T> {
+    unsafe fn to_wren(&self, vm: VMPtr, slot: Slot) {
+        vm.set_slot_new_list_unchecked(slot);
+        let item_slot = slot + 1;
+        for item in self {
+            item.to_wren(vm, item_slot);
+            vm.insert_in_list(slot, -1, item_slot);
+        }
+    }
 }
+impl<T: FromWren> FromWren for Vec<T> {
+    unsafe fn from_wren(vm: VMPtr, slot: Slot) -> Self {
+        let item_slot = slot + 1;
+        (0..vm.get_list_count_unchecked(slot))
+            .map(|i| {
+                vm.get_list_element_unchecked(slot, i, item_slot);
+                T::from_wren(vm, item_slot)
+            })
+            .collect()
+    }
+}
+
+/// A group of [`ToWren`] values written into consecutive slots starting at
+/// slot 0 -- typically a call's receiver followed by its arguments.
+pub trait ToWrenArgs {
+    const TOTAL_REQUIRED_SLOTS: Slot;
+    /// SAFETY: `vm` must have at least `Self::TOTAL_REQUIRED_SLOTS` slots reserved
+    unsafe fn set_slots(&self, vm: VMPtr);
+}
+
+const fn max_slot(a: Slot, b: Slot) -> Slot {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+macro_rules! total_required_slots {
+    (@step $offset:expr, $x:ty) => (<$x as WrenValue>::REQUIRED_SLOTS + $offset);
+    (@step $offset:expr, $x:ty, $($y:ty),+ $(,)?) => (
+        max_slot(
+            <$x as WrenValue>::REQUIRED_SLOTS + $offset,
+            total_required_slots!(@step $offset + 1, $($y),+),
+        )
+    );
+    ($x:ty $(, $y:ty)* $(,)?) => (
+        total_required_slots!(@step 0, $x $(, $y)*)
+    );
+}
+
+macro_rules! impl_to_wren_args {
+    ($( $xs:ident = $i:tt ), *) => {
+        impl<$( $xs: ToWren, )*> ToWrenArgs for ($( $xs, )*) {
+            const TOTAL_REQUIRED_SLOTS: Slot = total_required_slots!($( $xs ), *);
+            unsafe fn set_slots(&self, vm: VMPtr) {
+                $( self.$i.to_wren(vm, $i); )*
+            }
+        }
+    };
+}
+
+impl_to_wren_args!(T0 = 0);
+impl_to_wren_args!(T0 = 0, T1 = 1);
+impl_to_wren_args!(T0 = 0, T1 = 1, T2 = 2);
+impl_to_wren_args!(T0 = 0, T1 = 1, T2 = 2, T3 = 3);
 
 #[derive(Debug)]
 pub struct ErrorContext<'s> {
@@ -306,6 +653,8 @@ pub enum InterpretResultErrorKind {
     Compile,
     Runtime,
     Unknown(WrenInterpretResult),
+    /// [`Handle::call`] refused to run because the handle failed validation.
+    InvalidHandle(HandleError),
 }
 
 impl InterpretResultErrorKind {
@@ -360,6 +709,11 @@ pub struct Vm<V> {
 
 impl<V> Drop for Vm<V> {
     fn drop(&mut self) {
+        // Drop this VM's handle registry first: once `wrenFreeVM` returns, a
+        // later allocation is free to reuse this VM's address, and a
+        // registry entry left behind under that address would let a stale
+        // (index, generation) pair validate against the new VM.
+        handle::drop_registry(self.vm);
         unsafe { wrenFreeVM(self.vm.0.as_ptr()) }
     }
 }
@@ -381,6 +735,7 @@ where
             config.loadModuleFn = Some(load_module::<V>);
             config.resolveModuleFn = Some(resolve_module::<V>);
             config.bindForeignMethodFn = Some(bind_foreign_method::<V>);
+            config.bindForeignClassFn = Some(bind_foreign_class::<V>);
             config.userData = user_data.as_ptr().cast::<c_void>();
 
             let vm = VMPtr(NonNull::new(wrenNewVM(&mut config))?);
@@ -0,0 +1,109 @@
+#![allow(unsafe_code)]
+
+use std::{collections::HashMap, ffi::CString, path::PathBuf};
+
+use super::VmUserData;
+
+/// Resolves relative imports before handing `name` off to
+/// [`VmUserData::load_module`].
+///
+/// Wren passes through whatever string follows `import` verbatim; this
+/// interprets a `name` beginning with `./` or `../` against `importer` (the
+/// path of the module doing the importing), joining path segments and
+/// collapsing `..` the way a relative filesystem import would. A `..` that
+/// would walk above the root returns `None` instead of escaping it. Names
+/// that don't start with `./` or `../` are returned unchanged, matching
+/// [`VmUserData::resolve_module`]'s default behavior.
+pub fn resolve_relative_import(importer: &str, name: &str) -> Option<CString> {
+    if !name.starts_with("./") && !name.starts_with("../") {
+        return CString::new(name).ok();
+    }
+
+    // Drop the importer's own file name, keeping just its directory.
+    let mut segments: Vec<&str> = importer.split('/').collect();
+    segments.pop();
+
+    for segment in name.split('/') {
+        match segment {
+            "." | "" => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    CString::new(segments.join("/")).ok()
+}
+
+/// A [`VmUserData`] that resolves relative imports with
+/// [`resolve_relative_import`] and loads module source from a configurable
+/// set of filesystem roots, caching each module's source the first time
+/// it's read.
+///
+/// Roots are searched in the order they were added with
+/// [`FsModuleLoader::add_root`]; a module whose resolved path canonicalizes
+/// to somewhere outside the root it was found under is treated as missing,
+/// so a `..` that slips past [`resolve_relative_import`] still can't read
+/// outside the configured roots.
+pub struct FsModuleLoader {
+    roots: Vec<PathBuf>,
+    cache: HashMap<String, CString>,
+}
+
+impl FsModuleLoader {
+    pub fn new() -> Self {
+        Self {
+            roots: Vec::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Adds a filesystem root to search for modules, in the order added.
+    #[must_use]
+    pub fn add_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    fn read_module(&mut self, name: &str) -> Option<CString> {
+        if let Some(source) = self.cache.get(name) {
+            return Some(source.clone());
+        }
+
+        for root in &self.roots {
+            let Ok(root) = root.canonicalize() else {
+                continue;
+            };
+
+            let path = root.join(format!("{name}.wren"));
+            let source = match path.canonicalize() {
+                Ok(path) if path.starts_with(&root) => std::fs::read_to_string(path).ok(),
+                _ => None,
+            };
+
+            if let Some(source) = source.and_then(|source| CString::new(source).ok()) {
+                self.cache.insert(name.to_string(), source.clone());
+                return Some(source);
+            }
+        }
+
+        None
+    }
+}
+
+impl Default for FsModuleLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VmUserData for FsModuleLoader {
+    fn resolve_module(&mut self, resolver: &str, name: &str) -> Option<CString> {
+        resolve_relative_import(resolver, name)
+    }
+
+    fn load_module(&mut self, name: &str) -> Option<CString> {
+        self.read_module(name)
+    }
+}
@@ -0,0 +1,524 @@
+#![allow(unsafe_code)]
+
+use std::{cell::Cell, collections::HashMap, future::Future, pin::Pin, rc::Rc, time::Instant};
+
+use futures_lite::FutureExt;
+use tokio::sync::oneshot;
+
+use super::{Handle, ToWren, VMPtr};
+
+type BoxFuture = Pin<Box<dyn 'static + Future<Output = ()>>>;
+type TaskId = u64;
+
+/// Abstracts over the executor [`Scheduler::run_async_loop`] uses to drive
+/// its queued tasks, so an embedder that only needs a handful of timers
+/// isn't forced to pull in a full multi-threaded `tokio` runtime just to
+/// get `spawn_local` and a `LocalSet`.
+///
+/// The two halves mirror how `run_async_loop` already used `tokio` before
+/// this trait existed: [`SchedulerRuntime::spawn_local`] hands off a
+/// `!Send` future to run concurrently with whatever
+/// [`SchedulerRuntime::block_on`] is currently driving, and `block_on`
+/// drives one future (the receive loop) to completion while polling
+/// everything spawned alongside it.
+pub trait SchedulerRuntime {
+    /// Spawns `future` to run concurrently. Must only be called from within
+    /// a [`SchedulerRuntime::block_on`] call on the same runtime.
+    fn spawn_local(&self, future: BoxFuture);
+
+    /// Drives `future` to completion, polling any tasks spawned with
+    /// [`SchedulerRuntime::spawn_local`] alongside it.
+    fn block_on(&self, future: Pin<Box<dyn Future<Output = ()> + '_>>);
+}
+
+/// The default [`SchedulerRuntime`], backed by a `tokio` [`LocalSet`] run on
+/// a caller-supplied [`tokio::runtime::Runtime`].
+///
+/// [`LocalSet`]: tokio::task::LocalSet
+pub struct TokioRuntime<'a> {
+    runtime: &'a tokio::runtime::Runtime,
+    local_set: tokio::task::LocalSet,
+}
+
+impl<'a> TokioRuntime<'a> {
+    #[must_use]
+    pub fn new(runtime: &'a tokio::runtime::Runtime) -> Self {
+        Self {
+            runtime,
+            local_set: tokio::task::LocalSet::new(),
+        }
+    }
+}
+
+impl SchedulerRuntime for TokioRuntime<'_> {
+    fn spawn_local(&self, future: BoxFuture) {
+        // Valid because `block_on` always drives this spawn inside
+        // `self.local_set.run_until`.
+        tokio::task::spawn_local(future);
+    }
+
+    fn block_on(&self, future: Pin<Box<dyn Future<Output = ()> + '_>>) {
+        self.runtime.block_on(self.local_set.run_until(future));
+    }
+}
+
+/// An alternative [`SchedulerRuntime`] backed by `async-executor`'s
+/// single-threaded executor, for embedders running many short-lived
+/// timer/IO tasks who'd rather not pay `tokio`'s per-spawn overhead. Gated
+/// behind the `smol-runtime` feature since most embedders only need
+/// [`TokioRuntime`].
+///
+/// The executor is leaked once, up front, so it can outlive every future
+/// spawned onto it without `Scheduler` having to thread a lifetime through
+/// every task -- the same trick `tokio`'s own `spawn_local` plays by living
+/// behind a thread-local.
+#[cfg(feature = "smol-runtime")]
+pub struct SmolRuntime {
+    executor: &'static async_executor::LocalExecutor<'static>,
+}
+
+#[cfg(feature = "smol-runtime")]
+impl SmolRuntime {
+    #[must_use]
+    pub fn new() -> Self {
+        let executor = Box::leak(Box::new(async_executor::LocalExecutor::new()));
+        Self { executor }
+    }
+}
+
+#[cfg(feature = "smol-runtime")]
+impl Default for SmolRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "smol-runtime")]
+impl SchedulerRuntime for SmolRuntime {
+    fn spawn_local(&self, future: BoxFuture) {
+        self.executor.spawn(future).detach();
+    }
+
+    fn block_on(&self, future: Pin<Box<dyn Future<Output = ()> + '_>>) {
+        futures_lite::future::block_on(self.executor.run(future));
+    }
+}
+
+/// Identifies a task scheduled with [`Scheduler::schedule_task`], so it can
+/// later be passed to [`Scheduler::cancel`]. Carries no borrow on the
+/// scheduler itself, so it can be handed out to a fiber and outlive the
+/// `schedule_task` call that created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskHandle(TaskId);
+
+impl TaskHandle {
+    /// Encodes this handle as an `f64` so it can be handed to a Wren script
+    /// (e.g. as a timer's return value) and later passed back to
+    /// [`TaskHandle::from_raw`] to cancel it.
+    #[must_use]
+    pub fn into_raw(self) -> f64 {
+        // Safe for any TaskId a process could plausibly hand out before
+        // running out of memory to track them: f64 represents integers
+        // exactly up to 2^53.
+        #[allow(clippy::cast_precision_loss)]
+        let id = self.0 as f64;
+        id
+    }
+
+    /// Reconstructs a [`TaskHandle`] from a value previously returned by
+    /// [`TaskHandle::into_raw`].
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn from_raw(id: f64) -> Self {
+        Self(id as TaskId)
+    }
+}
+
+/// How a scheduled task finished, passed to its `on_complete` callback so it
+/// can tell a clean result from one that needs to become a Wren error.
+/// There's no `Cancelled` variant here -- a cancelled task's `on_complete`
+/// never runs at all.
+pub enum TaskResult {
+    /// The future resolved normally.
+    Completed,
+    /// The future panicked while [`Scheduler::propagate_panics`] was
+    /// enabled; carries the panic payload, formatted as a string.
+    Panicked(String),
+}
+
+/// What became of a scheduled task once [`Scheduler::run_async_loop`]
+/// finished driving it.
+enum TaskOutcome {
+    Completed,
+    Cancelled,
+    Panicked(String),
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "task panicked with a non-string payload".to_string()
+    }
+}
+
+/// A caller-supplied label plus scheduler-tracked timing for one task still
+/// outstanding in a [`Scheduler`], returned by [`Scheduler::inspect`]. Meant
+/// as a diagnostic surface for an embedder stuck on e.g. `awaitAll` with no
+/// other way to see what's still pending -- borrows the idea from
+/// `async-task`'s per-task `metadata`/`ScheduleInfo`.
+pub struct TaskMeta {
+    /// The label passed to [`Scheduler::schedule_task`], if any.
+    pub label: Option<String>,
+    /// When [`Scheduler::schedule_task`] queued this task.
+    pub enqueued_at: Instant,
+    polled: Rc<Cell<bool>>,
+}
+
+impl TaskMeta {
+    /// Whether this task has been polled at least once. A task that's been
+    /// queued a long time but never polled points at the scheduler's own
+    /// loop being stuck, rather than at the task's future itself.
+    #[must_use]
+    pub fn polled(&self) -> bool {
+        self.polled.get()
+    }
+}
+
+/// One outstanding async operation: a future to drive to completion, and the
+/// continuation that resumes the fiber which started it once it's done.
+struct Task<'vm> {
+    id: TaskId,
+    cancelled: oneshot::Receiver<()>,
+    future: BoxFuture,
+    resume: Box<dyn 'vm + FnOnce(VMPtr, TaskResult)>,
+    polled: Rc<Cell<bool>>,
+}
+
+/// A reusable async task queue that foreign methods suspend a Wren fiber
+/// against, instead of each one (timers, I/O, channels, ...) hand-rolling its
+/// own "wait, then resume the fiber" bookkeeping.
+///
+/// A foreign method captures the calling fiber's [`Handle`] and hands a
+/// [`Future`] plus a completion callback to [`Scheduler::schedule_task`];
+/// [`Scheduler::run_async_loop`] then drives every outstanding future to
+/// completion and runs each one's callback -- which typically resumes its
+/// fiber via [`Scheduler::resume`], [`Scheduler::resume_with_value`], or
+/// [`Scheduler::resume_with_error`] -- as it finishes.
+///
+/// [`Scheduler::schedule_task`] returns a [`TaskHandle`] so a task can be torn
+/// down with [`Scheduler::cancel`] before it resolves, borrowing the
+/// cancellation model `async-task` uses: cancelling drops the future without
+/// polling it to completion, and its `on_complete` callback never runs.
+pub struct Scheduler<'vm> {
+    vm: VMPtr,
+    // Call handles bound to the Wren-side methods that resume a suspended
+    // fiber with no value, with a value, and with an error message.
+    resume1: Handle<'vm>,
+    resume2: Handle<'vm>,
+    resume_error: Handle<'vm>,
+    queue: Vec<Task<'vm>>,
+    next_task_id: TaskId,
+    // Holds the sending half of each in-flight task's cancellation channel,
+    // so `cancel` can signal it by id. Removed once the task completes or is
+    // cancelled, so this only ever tracks tasks still outstanding.
+    cancel_senders: HashMap<TaskId, oneshot::Sender<()>>,
+    // Mirrors the lifetime of `cancel_senders`: one entry per task from
+    // `schedule_task` until it completes or is cancelled. Kept separate so
+    // `inspect` can hand out `&TaskMeta`s without exposing the cancellation
+    // channels alongside them.
+    task_meta: HashMap<TaskId, TaskMeta>,
+    // Whether a panicking task surfaces as a Wren runtime error through
+    // `TaskResult::Panicked` (see `propagate_panics`) instead of unwinding
+    // and taking `run_async_loop` down with it, as it always has.
+    propagate_panics: bool,
+}
+
+impl<'vm> Scheduler<'vm> {
+    pub fn new(
+        vm: VMPtr,
+        resume1: Handle<'vm>,
+        resume2: Handle<'vm>,
+        resume_error: Handle<'vm>,
+    ) -> Self {
+        Self {
+            vm,
+            resume1,
+            resume2,
+            resume_error,
+            queue: Vec::new(),
+            next_task_id: 0,
+            cancel_senders: HashMap::new(),
+            task_meta: HashMap::new(),
+            propagate_panics: false,
+        }
+    }
+
+    /// Chooses whether a task whose future panics surfaces as a Wren
+    /// runtime error (`on_complete` runs with [`TaskResult::Panicked`])
+    /// instead of unwinding out of [`Scheduler::run_async_loop`] and
+    /// aborting the process, which is the default and matches the scheduler's
+    /// historical behavior for embedders that want hard failures to stay
+    /// hard.
+    #[must_use]
+    pub fn propagate_panics(mut self, value: bool) -> Self {
+        self.propagate_panics = value;
+        self
+    }
+
+    /// Suspends the calling fiber: queues `future` under `label` (a
+    /// caller-chosen name surfaced through [`Scheduler::inspect`] for
+    /// debugging, e.g. `"Timer.sleep(500ms)"`), and once it resolves runs
+    /// `on_complete` against the VM. This is the hook a foreign method uses
+    /// to turn a Rust future into something Wren can `await` -- capture the
+    /// fiber's [`Handle`] before returning, then resume it from within
+    /// `on_complete`.
+    ///
+    /// The returned [`TaskHandle`] can be passed to [`Scheduler::cancel`] to
+    /// abandon the task before `future` resolves, in which case
+    /// `on_complete` never runs.
+    pub fn schedule_task<F, C>(
+        &mut self,
+        future: F,
+        label: Option<String>,
+        on_complete: C,
+    ) -> TaskHandle
+    where
+        F: 'static + Future<Output = ()>,
+        C: 'vm + FnOnce(VMPtr, TaskResult),
+    {
+        self.push_task(future, label, on_complete)
+    }
+
+    /// Suspends many fibers at once: like calling [`Scheduler::schedule_task`]
+    /// (with no label) once per `(future, on_complete)` pair in `tasks`, but
+    /// reserving space for the whole batch up front instead of growing
+    /// `self.queue` and its bookkeeping maps one task at a time. Meant for a
+    /// foreign method that enqueues a burst of work in a single call --
+    /// e.g. `Timer.startAllTimers_` in the `timer` module -- the same motivation behind
+    /// `async-executor`'s `spawn_batch` over repeated `spawn`.
+    pub fn schedule_batch<I, F, C>(&mut self, tasks: I) -> Vec<TaskHandle>
+    where
+        I: IntoIterator<Item = (F, C)>,
+        I::IntoIter: ExactSizeIterator,
+        F: 'static + Future<Output = ()>,
+        C: 'vm + FnOnce(VMPtr, TaskResult),
+    {
+        let tasks = tasks.into_iter();
+        let len = tasks.len();
+        self.queue.reserve(len);
+        self.cancel_senders.reserve(len);
+        self.task_meta.reserve(len);
+
+        tasks
+            .map(|(future, on_complete)| self.push_task(future, None, on_complete))
+            .collect()
+    }
+
+    /// Shared by [`Scheduler::schedule_task`] and [`Scheduler::schedule_batch`]:
+    /// assigns the next [`TaskId`], records its [`TaskMeta`], and queues it.
+    fn push_task<F, C>(&mut self, future: F, label: Option<String>, on_complete: C) -> TaskHandle
+    where
+        F: 'static + Future<Output = ()>,
+        C: 'vm + FnOnce(VMPtr, TaskResult),
+    {
+        let id = self.next_task_id;
+        self.next_task_id += 1;
+
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        self.cancel_senders.insert(id, cancel_tx);
+
+        let polled = Rc::new(Cell::new(false));
+        self.task_meta.insert(
+            id,
+            TaskMeta {
+                label,
+                enqueued_at: Instant::now(),
+                polled: Rc::clone(&polled),
+            },
+        );
+
+        self.queue.push(Task {
+            id,
+            cancelled: cancel_rx,
+            future: Box::pin(future),
+            resume: Box::new(on_complete),
+            polled,
+        });
+
+        TaskHandle(id)
+    }
+
+    /// Cancels a task scheduled with [`Scheduler::schedule_task`] before its
+    /// future resolves: the future is dropped without being polled to
+    /// completion, and its `on_complete` callback never runs. Returns `false`
+    /// if `task` already completed or was already cancelled.
+    pub fn cancel(&mut self, task: TaskHandle) -> bool {
+        self.task_meta.remove(&task.0);
+        self.cancel_senders
+            .remove(&task.0)
+            .map_or(false, |cancel_tx| cancel_tx.send(()).is_ok())
+    }
+
+    /// Iterates the label and timing of every task currently queued or
+    /// in-flight, for an embedder to log or inspect when e.g. `awaitAll`
+    /// looks like it's hung -- a task whose [`TaskMeta::polled`] is `false`
+    /// long after [`TaskMeta::enqueued_at`] suggests the scheduler's own
+    /// loop isn't running, rather than the task itself being slow.
+    pub fn inspect(&self) -> impl Iterator<Item = &TaskMeta> {
+        self.task_meta.values()
+    }
+
+    /// Hands every task currently sitting in `self.queue` to `runtime`,
+    /// stashing each one's `resume` callback in `resumes` (keyed by task id)
+    /// and reporting its id and outcome back over `tx` once it's done.
+    ///
+    /// Reserves `resumes`' capacity for the whole of `self.queue` up front
+    /// -- this is usually called right after [`Scheduler::schedule_batch`]
+    /// queued a burst of tasks in one go, and inserting them one at a time
+    /// without reserving first would make `resumes` repeatedly reallocate
+    /// and rehash as it grows.
+    fn spawn_queued(
+        &mut self,
+        runtime: &dyn SchedulerRuntime,
+        tx: &tokio::sync::mpsc::Sender<(TaskId, TaskOutcome)>,
+        resumes: &mut HashMap<TaskId, Box<dyn 'vm + FnOnce(VMPtr, TaskResult)>>,
+    ) {
+        resumes.reserve(self.queue.len());
+
+        for Task {
+            id,
+            cancelled,
+            future,
+            resume,
+            polled,
+        } in self.queue.drain(..)
+        {
+            resumes.insert(id, resume);
+
+            let tx = tx.clone();
+            let propagate_panics = self.propagate_panics;
+            runtime.spawn_local(Box::pin(async move {
+                // Set on this task's very first poll, before `drive` (and
+                // therefore `future`) gets a chance to run -- an async
+                // block's body runs synchronously up to its first
+                // suspension point as soon as it's first polled.
+                polled.set(true);
+
+                let drive = async move {
+                    // Checked first, so a cancel sent before this task was
+                    // spawned wins even if `future` is already ready.
+                    tokio::select! {
+                        biased;
+                        _ = cancelled => TaskOutcome::Cancelled,
+                        () = future => TaskOutcome::Completed,
+                    }
+                };
+
+                // `catch_unwind` wraps every individual poll of `drive`, so
+                // it catches a panic that happens partway through an
+                // awaited `future` just as well as one that happens
+                // synchronously -- unlike wrapping the whole `.await` in one
+                // `std::panic::catch_unwind` call, it doesn't need `drive`
+                // to be driven to completion inside a single, non-async
+                // closure to work. It doesn't rely on the executor either, so
+                // it works the same under any `SchedulerRuntime`.
+                let outcome = if propagate_panics {
+                    match std::panic::AssertUnwindSafe(drive).catch_unwind().await {
+                        Ok(outcome) => outcome,
+                        Err(payload) => TaskOutcome::Panicked(panic_message(&payload)),
+                    }
+                } else {
+                    drive.await
+                };
+
+                tx.send((id, outcome))
+                    .await
+                    .expect("Channel shoudn't fail to send");
+            }));
+        }
+    }
+
+    /// Drives every outstanding task to completion, running each one's
+    /// `on_complete` callback as it finishes (unless it was cancelled).
+    ///
+    /// Modeled after `juggle`'s single-thread round-robin scheduler: rather
+    /// than waiting for a whole batch of tasks to finish before looking at
+    /// what they scheduled next, this keeps a single receive loop running
+    /// for as long as any task is outstanding, draining and spawning
+    /// `self.queue` again right after *every* completion instead of only
+    /// between batches. That's what lets a fiber chain several
+    /// `Timer.sleep` calls back to back -- the next `sleep` it schedules
+    /// from inside `on_complete` gets picked up immediately rather than
+    /// waiting on the rest of its original batch -- and lets a fast task's
+    /// callback run ahead of a slower one that was enqueued earlier.
+    ///
+    /// `runtime` picks the executor this drives the queue with -- see
+    /// [`SchedulerRuntime`], [`TokioRuntime`], and (behind the
+    /// `smol-runtime` feature) [`SmolRuntime`].
+    pub fn run_async_loop(&mut self, runtime: &dyn SchedulerRuntime) {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(128);
+
+        let mut resumes: HashMap<TaskId, Box<dyn 'vm + FnOnce(VMPtr, TaskResult)>> =
+            HashMap::new();
+
+        runtime.block_on(Box::pin(async {
+            self.spawn_queued(runtime, &tx, &mut resumes);
+
+            while !resumes.is_empty() {
+                let Some((id, outcome)) = rx.recv().await else {
+                    break;
+                };
+                self.cancel_senders.remove(&id);
+                self.task_meta.remove(&id);
+
+                if let Some(resume) = resumes.remove(&id) {
+                    match outcome {
+                        TaskOutcome::Completed => resume(self.vm, TaskResult::Completed),
+                        TaskOutcome::Cancelled => {}
+                        TaskOutcome::Panicked(message) => {
+                            resume(self.vm, TaskResult::Panicked(message));
+                        }
+                    }
+                }
+
+                // The callback just run may have scheduled more tasks (e.g.
+                // a fiber chaining another `Timer.sleep`); pick those up
+                // before waiting on the next completion instead of only at
+                // the top of a batch.
+                self.spawn_queued(runtime, &tx, &mut resumes);
+            }
+        }));
+    }
+
+    /// Resumes `fiber` with no value.
+    ///
+    /// SAFETY: `fiber` must be a handle to a fiber currently suspended
+    /// waiting on this scheduler.
+    pub unsafe fn resume(&self, fiber: &Handle<'vm>) {
+        self.resume1
+            .call::<_, ()>((fiber,))
+            .expect("Fiber errored after resuming.");
+    }
+
+    /// Resumes `fiber`, passing `value` back as the result of the
+    /// expression it suspended on.
+    ///
+    /// SAFETY: see [`Scheduler::resume`].
+    pub unsafe fn resume_with_value<T: ToWren>(&self, fiber: &Handle<'vm>, value: T) {
+        self.resume2
+            .call::<_, ()>((fiber, value))
+            .expect("Fiber errored after resuming.");
+    }
+
+    /// Resumes `fiber` by raising `message` as a runtime error inside it.
+    ///
+    /// SAFETY: see [`Scheduler::resume`].
+    pub unsafe fn resume_with_error<S: AsRef<str>>(&self, fiber: &Handle<'vm>, message: S) {
+        self.resume_error
+            .call::<_, ()>((fiber, message.as_ref().to_string()))
+            .expect("Fiber errored after resuming.");
+    }
+}